@@ -1,9 +1,8 @@
-use crate::gzip::gzip_inflate;
+use crate::gzip::{inflate, Encoding};
 use crate::http;
+use crate::json;
 use crate::Error;
 
-use crate::search::find_pattern;
-
 #[derive(Debug, PartialEq, Clone)]
 pub enum ServerStatus {
     Online,
@@ -11,83 +10,18 @@ pub enum ServerStatus {
     Unknown(String),
 }
 
-/// Performs some basic JSON validation and eliminates whitespace outside of
-/// JSON strings. Ensures correct parity for braces, brackets, and quotes,
-/// with escaped characters ignored,, so strings with escaped quotes are 
-/// accounted for.
-
-pub fn sanitize_json(data: Vec<u8>) -> Option<Vec<u8>> {
-    let mut ordering_stack: Vec<u8> = Vec::new();
-
-    let mut escaping: bool = false;
-    let mut quoting: bool = false;
-
-    let mut stripped: Vec<u8> = Vec::new();
-
-    for byte in data.iter() {
-        match byte {
-            _ if escaping => {
-                escaping = false;
-            }
-
-            b'\\' => {
-                escaping = true;
-            }
-
-            b'"' => {
-                quoting ^= true;
-            }
-
-            _ if quoting => (),
-
-            b'{' | b'[' => ordering_stack.push(*byte),
-            b'}' | b']' => {
-                if let Some(stack_byte) = ordering_stack.pop() {
-                    if (byte - 2) != stack_byte {
-                        return None;
-                    }
-                } else {
-                    return None;
-                }
-            }
-            _ => (),
-        }
-
-        if !byte.is_ascii_whitespace() {
-            stripped.push(*byte);
-        }
-    }
-
-    (ordering_stack.is_empty() && !quoting && !escaping).then_some(stripped)
-}
-
-/// Sarches for the first occurence of a JSON key, and extracts its value with
-/// the assumption that the value is a string.
-pub fn extract_json_str(json_data: &Vec<u8>, key: &str) -> Result<String, Error> {
-    let pattern = format!("\"{}\":\"", key);
-
-    let json_data = sanitize_json(json_data.clone()).ok_or(Error::InvalidJson)?;
-
-    let (beg, _) = find_pattern(&pattern.as_bytes().to_vec(), &json_data.clone())
-        .ok_or(Error::NoPattern(json_data.clone()))?;
-
-    let remaining = json_data.iter().skip(beg + pattern.len());
-
-    let value: String = remaining
-        .take_while(|&&b| b != b'"')
-        .map(|&b| b as char)
-        .collect();
-
-    Ok(value)
-}
-
 /// Checks the server status of the Star Trek Online game server.
 pub fn check_server_status() -> Result<ServerStatus, Error> {
     let domain = "startreklauncher.crypticstudios.com";
 
     let headers: Vec<(String, String)> = vec![
         ("Host", "startreklauncher.crypticstudios.com"),
-        ("Connection", "keep-alive"),
+        // A one-shot request on its own TcpStream has no connection to keep
+        // alive; asking to close lets `Response::is_complete` fall back on
+        // the peer closing the socket when there's no Content-Length or
+        // chunked encoding to delimit the body, instead of blocking until
+        // the read times out.
+        ("Connection", "close"),
         ("Content-Length", "0"),
         ("Accept", "application/json, text/javascript, */*, q=0.01"),
         ("User-Agent", "Mozilla/4.0 (compatible, CrypticLauncher)"),
@@ -115,16 +49,28 @@ pub fn check_server_status() -> Result<ServerStatus, Error> {
         None,
     );
 
-    // Eliminate all whitespace, and downcase the response data, to ensure
-    // consistency when searching for the relevant data.
-    let data = request
-        .send()?
-        .gz_extract()
-        .map(|mut gz| unsafe { gzip_inflate(&mut gz) })?
+    let response = request.send()?;
+
+    if !(200..300).contains(&response.status()) {
+        return Ok(ServerStatus::Unknown(format!(
+            "http status {}",
+            response.status()
+        )));
+    }
+
+    let encoding = Encoding::from_content_encoding(response.header("content-encoding"), response.body());
+
+    let data = unsafe { inflate(&mut response.body().to_vec(), encoding) }
         .map_err(|_| Error::InvalidGZip)?;
 
 
-    let server_status = extract_json_str(&data, "server_status")?;
+    let parsed = json::parse(&data).map_err(|_| Error::InvalidJson)?;
+
+    let server_status: String = parsed
+        .get_path(&["server_status"])
+        .ok_or(Error::InvalidJson)?
+        .get::<String>()
+        .map_err(|_| Error::InvalidJson)?;
 
     match server_status.as_str() {
         "up" => Ok(ServerStatus::Online),
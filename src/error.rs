@@ -4,9 +4,10 @@
 pub enum Error {
     IoError(std::io::Error),
     TooMuchData(usize),
-    NoPattern(Vec<u8>),
     NoData,
     InvalidJson,
-    InvalidGZip
+    InvalidGZip,
+    InvalidResponse(String),
+    TlsError(String),
 }
 
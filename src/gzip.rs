@@ -35,11 +35,57 @@ extern "C" {
         version: *const c_char,
         stream_size: c_int,
     ) -> c_int;
-    fn inflate(strm: *mut ZStream, flush: c_int) -> c_int;
+    #[link_name = "inflate"]
+    fn zlib_inflate(strm: *mut ZStream, flush: c_int) -> c_int;
     fn inflateEnd(strm: *mut ZStream) -> c_int;
 }
 
-pub unsafe fn gzip_inflate(compressed: &mut Vec<u8>) -> Result<Vec<u8>, io::Error> {
+/// Which wire format `inflate` should configure zlib for. The three map to
+/// the `windowBits` values zlib's `inflateInit2_` expects: gzip auto-detects
+/// its own header, zlib wraps the deflate stream in a 2-byte header plus an
+/// Adler-32 trailer, and raw deflate has no framing of its own at all.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Gzip,
+    Zlib,
+    RawDeflate,
+}
+
+impl Encoding {
+    fn window_bits(self) -> c_int {
+        match self {
+            // 15 is the default, 32 is the "enable gzip decoding" flag
+            Encoding::Gzip => 15 + 32,
+            Encoding::Zlib => 15,
+            Encoding::RawDeflate => -15,
+        }
+    }
+
+    /// Picks an `Encoding` from a `Content-Encoding` header value, falling
+    /// back to sniffing `data`'s leading bytes (the `1f 8b` gzip magic
+    /// number vs. a zlib header byte) when the header is missing or names
+    /// something other than `gzip`/`deflate`.
+    pub fn from_content_encoding(content_encoding: Option<&str>, data: &[u8]) -> Self {
+        match content_encoding.map(|v| v.trim().to_ascii_lowercase()) {
+            Some(v) if v == "gzip" || v == "x-gzip" => Encoding::Gzip,
+            // Most servers that advertise `Content-Encoding: deflate` still
+            // send a zlib-wrapped stream, so that's the default; raw
+            // deflate is only picked up by sniffing below.
+            Some(v) if v == "deflate" => Encoding::Zlib,
+            _ => Self::sniff(data),
+        }
+    }
+
+    fn sniff(data: &[u8]) -> Self {
+        match data {
+            [0x1f, 0x8b, ..] => Encoding::Gzip,
+            [first, ..] if first & 0x0f == 8 => Encoding::Zlib,
+            _ => Encoding::RawDeflate,
+        }
+    }
+}
+
+pub unsafe fn inflate(compressed: &mut Vec<u8>, encoding: Encoding) -> Result<Vec<u8>, io::Error> {
     // let version = unsafe {
     //     let c_str = zlibVersion();
     //     CStr::from_ptr(c_str).to_str().unwrap()
@@ -65,13 +111,9 @@ pub unsafe fn gzip_inflate(compressed: &mut Vec<u8>) -> Result<Vec<u8>, io::Erro
         reserved: 0,
     };
 
-    // Initialize zlib
-    // 15 is the default, 32 is the "enable gzip decoding" flag
-    let window_bits = 15 + 32;
-
     let init_result = inflateInit2_(
         &mut z_stream,
-        window_bits,
+        encoding.window_bits(),
         CStr::from_bytes_with_nul(b"1.3.00\0").unwrap().as_ptr(),
         std::mem::size_of::<ZStream>() as c_int,
     );
@@ -91,32 +133,49 @@ pub unsafe fn gzip_inflate(compressed: &mut Vec<u8>) -> Result<Vec<u8>, io::Erro
     }
 
     let mut decompressed: Vec<u8> = vec![];
-    let mut buffer: Vec<u8> = vec![0; compressed.len() * 5];
 
-    // let mut buffer: [u8; 1024] = [0; 1024];
+    // Fixed-size scratch space for each round trip into zlib; `decompressed`
+    // itself is what grows, one `extend_from_slice` at a time, so pathological
+    // compression ratios just mean more rounds rather than a truncated result.
+    let mut buffer: Vec<u8> = vec![0; 4096];
 
     loop {
         z_stream.next_out = buffer.as_mut_ptr();
         z_stream.avail_out = buffer.len() as c_ulong;
 
-        let inflate_result = inflate(&mut z_stream, 0);
-
-        // Z_BUF_ERROR
-        if inflate_result == -5 {
-            let delta = buffer.len() - z_stream.avail_out as usize;
-            decompressed.extend_from_slice(&buffer[0..delta]);
-        } else if inflate_result != 0 && inflate_result != 1 {
-            // Error or incomplete stream.
-            return Err(io::Error::new(
-                io::ErrorKind::Other,
-                format!("Failed to inflate data: {}", inflate_result),
-            ));
-        }
-        // Decompression finished
-        else {
-            let bytes_written = buffer.len() - z_stream.avail_out as usize;
-            decompressed.extend_from_slice(&buffer[0..bytes_written]);
-            break;
+        let inflate_result = zlib_inflate(&mut z_stream, 0);
+
+        match inflate_result {
+            // Z_OK or Z_BUF_ERROR: progress was made (or the output buffer
+            // filled up) but the stream isn't finished yet, so keep looping.
+            // A truncated/corrupt stream can exhaust its input and still
+            // return Z_BUF_ERROR without writing anything, round after
+            // round; treat that combination as an error instead of
+            // spinning forever.
+            0 | -5 => {
+                let written = buffer.len() - z_stream.avail_out as usize;
+
+                if written == 0 && z_stream.avail_in == 0 {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "truncated or corrupt compressed data",
+                    ));
+                }
+
+                decompressed.extend_from_slice(&buffer[0..written]);
+            }
+            // Z_STREAM_END: decompression finished.
+            1 => {
+                let written = buffer.len() - z_stream.avail_out as usize;
+                decompressed.extend_from_slice(&buffer[0..written]);
+                break;
+            }
+            n => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    format!("Failed to inflate data: {}", n),
+                ));
+            }
         }
     }
 
@@ -1,4 +1,4 @@
-use crate::{error::Error, pattern::find_pattern};
+use crate::{error::Error, search::find_pattern, tls::TlsStream};
 
 use std::{
     collections::HashMap,
@@ -23,19 +23,222 @@ impl From<&Method> for String {
 
 #[derive(Debug, Clone)]
 pub struct Response {
-    data: Vec<u8>
-    // status_code: u16,
+    status: u16,
+    headers: HashMap<String, String>,
+    body: Vec<u8>,
 }
 
 impl Response {
+    pub fn status(&self) -> u16 {
+        self.status
+    }
+
+    /// Case-insensitive header lookup.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(&name.to_ascii_lowercase()).map(|v| v.as_str())
+    }
+
+    pub fn body(&self) -> &[u8] {
+        &self.body
+    }
+
+    /// Splits a raw socket read at the `\r\n\r\n` header terminator and
+    /// parses the status line and headers, returning the byte offset at
+    /// which the body begins alongside them.
+    fn parse_headers(raw: &[u8]) -> Result<(usize, u16, HashMap<String, String>), Error> {
+        let header_terminator: Vec<u8> = vec![0x0d, 0x0a, 0x0d, 0x0a];
+
+        let (term_beg, term_end) = find_pattern(&header_terminator, raw)
+            .ok_or_else(|| Error::InvalidResponse("no end of headers found".into()))?;
+
+        let header_block = &raw[..term_beg];
+        let body_start = term_end + 1;
+
+        let header_text = String::from_utf8_lossy(header_block);
+        let mut lines = header_text.split("\r\n");
+
+        let status_line = lines
+            .next()
+            .ok_or_else(|| Error::InvalidResponse("missing status line".into()))?;
+
+        let status = Self::parse_status_line(status_line)?;
+
+        let mut headers: HashMap<String, String> = HashMap::new();
+
+        for line in lines {
+            if let Some((name, value)) = line.split_once(':') {
+                headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+            }
+        }
+
+        Ok((body_start, status, headers))
+    }
+
+    /// Parses a complete raw socket read and decodes the body according to
+    /// `Transfer-Encoding`/`Content-Length` as described by the headers.
+    fn parse(raw: &[u8]) -> Result<Self, Error> {
+        let (body_start, status, headers) = Self::parse_headers(raw)?;
+        let raw_body = &raw[body_start..];
+
+        let is_chunked = headers
+            .get("transfer-encoding")
+            .map(|v| v.eq_ignore_ascii_case("chunked"))
+            .unwrap_or(false);
+
+        let body = if is_chunked {
+            Self::decode_chunked(raw_body)?
+        } else if let Some(len) = headers.get("content-length").and_then(|v| v.parse::<usize>().ok()) {
+            raw_body[..len.min(raw_body.len())].to_vec()
+        } else {
+            raw_body.to_vec()
+        };
+
+        Ok(Response {
+            status,
+            headers,
+            body,
+        })
+    }
+
+    /// Whether `data` already holds a full response: headers plus a body
+    /// that satisfies `Content-Length`, or a chunked body that has reached
+    /// its terminating zero-size chunk. Returns `false` (not an error) for
+    /// anything still in flight, including a response with neither header
+    /// present, which can only be known complete once the peer closes the
+    /// connection.
+    fn is_complete(data: &[u8]) -> bool {
+        let (body_start, _, headers) = match Self::parse_headers(data) {
+            Ok(parsed) => parsed,
+            Err(_) => return false,
+        };
+
+        let body = &data[body_start..];
+
+        let is_chunked = headers
+            .get("transfer-encoding")
+            .map(|v| v.eq_ignore_ascii_case("chunked"))
+            .unwrap_or(false);
+
+        if is_chunked {
+            let chunked_terminator: Vec<u8> = vec![b'0', 0x0d, 0x0a, 0x0d, 0x0a];
+            find_pattern(&chunked_terminator, body).is_some()
+        } else if let Some(len) = headers.get("content-length").and_then(|v| v.parse::<usize>().ok()) {
+            body.len() >= len
+        } else {
+            false
+        }
+    }
+
+    fn parse_status_line(line: &str) -> Result<u16, Error> {
+        let mut parts = line.split_whitespace();
+
+        parts
+            .next()
+            .ok_or_else(|| Error::InvalidResponse("empty status line".into()))?;
+
+        let code = parts
+            .next()
+            .ok_or_else(|| Error::InvalidResponse("status line missing code".into()))?;
+
+        code.parse::<u16>()
+            .map_err(|_| Error::InvalidResponse(format!("bad status code: {}", code)))
+    }
+
+    /// Walks a chunked-transfer body: a hex chunk-size line, `\r\n`, that
+    /// many bytes, `\r\n`, repeated until a zero-size chunk terminates it.
+    fn decode_chunked(data: &[u8]) -> Result<Vec<u8>, Error> {
+        let mut decoded: Vec<u8> = Vec::new();
+        let mut cursor = 0;
+
+        loop {
+            let size_line_len = data[cursor..]
+                .windows(2)
+                .position(|w| w == b"\r\n")
+                .ok_or_else(|| Error::InvalidResponse("unterminated chunk size".into()))?;
+
+            let size_line = std::str::from_utf8(&data[cursor..cursor + size_line_len])
+                .map_err(|_| Error::InvalidResponse("non-utf8 chunk size".into()))?;
+
+            // Chunk extensions (`;name=value`) are allowed after the size.
+            let size_str = size_line.split(';').next().unwrap_or(size_line).trim();
+
+            let size = usize::from_str_radix(size_str, 16)
+                .map_err(|_| Error::InvalidResponse(format!("bad chunk size: {}", size_str)))?;
+
+            cursor += size_line_len + 2;
+
+            if size == 0 {
+                break;
+            }
+
+            if cursor + size > data.len() {
+                return Err(Error::InvalidResponse("chunk overruns body".into()));
+            }
+
+            decoded.extend_from_slice(&data[cursor..cursor + size]);
+            cursor += size + 2;
+        }
+
+        Ok(decoded)
+    }
+}
 
-    pub fn gz_extract(&self) -> Result<Vec<u8>, Error> {
-        // let header_magic: Vec<u8> = vec![ 0x0d, 0x0a, 0x0d, 0x0a];
-        let gzip_magic_number: Vec<u8> = vec![0x1f, 0x8b, 0x08];
+/// Percent-encodes everything outside the unreserved set (`A-Z a-z 0-9 -
+/// _ . ~`) as `%XX`, so a path segment or query value can carry spaces,
+/// `&`, `=`, `/`, or non-ASCII bytes without corrupting the request line.
+fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
 
-        match find_pattern(&gzip_magic_number, &self.data) {
-            Some((beg, _)) => Ok(self.data[beg..].to_vec()),
-            None => Err(Error::NoData),
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char);
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+
+    encoded
+}
+
+/// Percent-encodes an endpoint path one segment at a time: every `/` in
+/// `path` is kept as a separator, and the bytes between separators are
+/// encoded with `percent_encode`.
+fn percent_encode_path(path: &str) -> String {
+    path.split('/')
+        .map(percent_encode)
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// The underlying connection a `Request` is sent over, picked by
+/// `Request::send` based on `Request::secure`.
+enum Transport {
+    Plain(TcpStream),
+    Tls(TlsStream),
+}
+
+impl Read for Transport {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            Transport::Plain(stream) => stream.read(buf),
+            Transport::Tls(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for Transport {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            Transport::Plain(stream) => stream.write(buf),
+            Transport::Tls(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            Transport::Plain(stream) => stream.flush(),
+            Transport::Tls(stream) => stream.flush(),
         }
     }
 }
@@ -47,14 +250,47 @@ pub struct Request {
     pub port: u16,
     pub method: Method,
     pub headers: HashMap<String, String>,
+
+    /// Whether to negotiate TLS before writing the HTTP payload. Defaults
+    /// the port to 443 in `Request::new_with_scheme` when set (`Request::new`
+    /// always leaves it unset), same as a browser
+    /// treating `https://` as implying a different default port than
+    /// `http://`.
+    pub secure: bool,
+
+    /// Upper bound on the total bytes `send` will accumulate from the
+    /// socket before giving up. Still guards against a peer streaming an
+    /// unbounded response, just no longer capped at one fixed-size read.
+    pub max_response_bytes: usize,
+
+    /// Query parameters appended to the endpoint, each percent-encoded and
+    /// joined as `?k=v&k2=v2` by `construct`.
+    pub query: Vec<(String, String)>,
 }
 
+/// Default `max_response_bytes`: generous enough for any legitimate gzipped
+/// status payload, small enough to bound a misbehaving or hostile peer.
+const DEFAULT_MAX_RESPONSE_BYTES: usize = 8 * 1024 * 1024;
+
 impl Request {
     pub fn construct(&self) -> String {
         let mut data = String::new();
 
-        data.push_str(format!("{} {}", String::from(&self.method), self.endpoint).as_str());
+        data.push_str(format!("{} {}", String::from(&self.method), percent_encode_path(&self.endpoint)).as_str());
         data.push_str(&self.url);
+
+        if !self.query.is_empty() {
+            let query_string = self
+                .query
+                .iter()
+                .map(|(key, value)| format!("{}={}", percent_encode(key), percent_encode(value)))
+                .collect::<Vec<_>>()
+                .join("&");
+
+            data.push('?');
+            data.push_str(&query_string);
+        }
+
         data.push_str(" HTTP/1.1\r\n");
 
         for (key, value) in &self.headers {
@@ -70,33 +306,46 @@ impl Request {
     }
 
     pub fn send(&self) -> Result<Response, Error> {
-        let mut stream = TcpStream::connect(format!("{}:{}", &self.url, self.port)).unwrap();
-        let http_payload = self.construct();
+        let tcp_stream = TcpStream::connect(format!("{}:{}", &self.url, self.port)).unwrap();
 
-        if let Err(e) = stream.write(http_payload.as_bytes()) {
-            return Err(Error::IoError(e));
-        }
+        let mut stream = if self.secure {
+            Transport::Tls(TlsStream::connect(tcp_stream, &self.url)?)
+        } else {
+            Transport::Plain(tcp_stream)
+        };
+
+        let http_payload = self.construct();
 
         stream
             .write(http_payload.as_bytes())
             .map_err(|e| Error::IoError(e))?;
 
-        // This is already a generous buffer size, but limited to prevent
-        // the other side from just streaming as much data as they want.
-        let mut buffer: [u8; 16384] = [0; 16384];
+        let mut data: Vec<u8> = Vec::new();
+        let mut chunk: [u8; 4096] = [0; 4096];
 
-        let nbytes_read: usize = stream.read(&mut buffer).map_err(|e| Error::IoError(e))?;
+        loop {
+            let nbytes_read = stream.read(&mut chunk).map_err(|e| Error::IoError(e))?;
 
-        if nbytes_read == 0 {
-            return Err(Error::NoData);
-        } else if nbytes_read >= buffer.len() {
-            return Err(Error::TooMuchData(nbytes_read));
+            if nbytes_read == 0 {
+                break;
+            }
+
+            data.extend_from_slice(&chunk[0..nbytes_read]);
+
+            if data.len() > self.max_response_bytes {
+                return Err(Error::TooMuchData(data.len()));
+            }
+
+            if Response::is_complete(&data) {
+                break;
+            }
         }
 
+        if data.is_empty() {
+            return Err(Error::NoData);
+        }
 
-        Ok(Response {
-            data: Vec::<u8>::from(&buffer[0..nbytes_read]),
-        })
+        Response::parse(&data)
     }
 
     pub fn new(
@@ -105,15 +354,44 @@ impl Request {
         method: Method,
         headers: Vec<(String, String)>,
         port: Option<u16>,
+    ) -> Self {
+        Self::new_with_scheme(url, endpoint, method, headers, port, false)
+    }
+
+    /// Same as `new`, but lets the caller opt into TLS. When `secure` is
+    /// true and `port` is `None`, the default port is 443 instead of 80.
+    pub fn new_with_scheme(
+        url: String,
+        endpoint: String,
+        method: Method,
+        headers: Vec<(String, String)>,
+        port: Option<u16>,
+        secure: bool,
     ) -> Self {
         let headers: HashMap<String, String> = headers.into_iter().collect();
 
         Self {
             url,
             endpoint,
-            port: port.unwrap_or(80),
+            port: port.unwrap_or(if secure { 443 } else { 80 }),
             method,
             headers,
+            secure,
+            max_response_bytes: DEFAULT_MAX_RESPONSE_BYTES,
+            query: Vec::new(),
         }
     }
+
+    /// Overrides the default cap on accumulated response bytes.
+    pub fn with_max_response_bytes(mut self, max_response_bytes: usize) -> Self {
+        self.max_response_bytes = max_response_bytes;
+        self
+    }
+
+    /// Sets the query parameters appended to the endpoint. Each key and
+    /// value is percent-encoded independently, so callers pass raw values.
+    pub fn with_query(mut self, query: Vec<(String, String)>) -> Self {
+        self.query = query;
+        self
+    }
 }
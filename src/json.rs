@@ -1,4 +1,59 @@
-use std::{collections::HashMap, str::Chars};
+use std::{collections::HashMap, iter::Peekable, str::Chars};
+
+/// The parser never needs to look more than one character ahead, but it
+/// does need to leave a value's trailing delimiter (`,`, `}`, `]`, or
+/// whitespace) unconsumed for whichever array/object loop called it, so
+/// every parsing function takes a peekable cursor rather than a plain
+/// `Chars`. It also tracks line, column, and byte offset as characters are
+/// consumed, so a parse error can report where in the source it happened.
+struct Cursor<'a> {
+    chars: Peekable<Chars<'a>>,
+    line: usize,
+    col: usize,
+    offset: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(source: &'a str) -> Self {
+        Self {
+            chars: source.chars().peekable(),
+            line: 1,
+            col: 1,
+            offset: 0,
+        }
+    }
+
+    fn peek(&mut self) -> Option<&char> {
+        self.chars.peek()
+    }
+
+    fn current_position(&self) -> Position {
+        Position {
+            line: self.line,
+            col: self.col,
+            offset: self.offset,
+        }
+    }
+}
+
+impl<'a> Iterator for Cursor<'a> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        let character = self.chars.next()?;
+
+        self.offset += character.len_utf8();
+
+        if character == '\n' {
+            self.line += 1;
+            self.col = 1;
+        } else {
+            self.col += 1;
+        }
+
+        Some(character)
+    }
+}
 
 // JSON
 //
@@ -68,7 +123,7 @@ pub enum JsonToken {
 
 /// Various errors that could arise while parsing JSON.
 #[derive(Clone, Debug)]
-pub enum JsonError {
+pub enum JsonErrorKind {
     /// Encountered a token that should not have been encountered
     /// in that context if the JSON were valid. This is the most
     /// Generic JsonError. Example, expecting "`:`" in "`{ "foo", 2 }`"
@@ -108,12 +163,59 @@ pub enum JsonError {
     // failed at being parsed into an integer via `parse::<i64>()`.
     InconvertibleToInt(String, std::num::ParseIntError),
 
+    /// A `\uXXXX` escape wasn't followed by four hex digits, or formed an
+    /// unpaired UTF-16 surrogate (a high surrogate not followed by a low
+    /// surrogate escape, or a low surrogate with no preceding high one).
+    BadUnicodeEscape(String),
+
+    /// A scientific-notation exponent (`e`/`E`) appeared more than once, or
+    /// wasn't placed directly after a digit. Invalid example: `1e2e3`
+    /// Valid example: `1.2e-45`
+    BadExponent,
+
     /// Expected a JsonValue to contain a type that it didn't contain.
     /// The first argument is the JsonValue, the second is the expected type,
     /// which the JsonValue did not contain.
     JsonValueNotType(JsonValue, String),
 }
 
+/// A line/column/byte-offset position in the source text, pointing at the
+/// next unread character when a `JsonError` was raised, i.e. just past the
+/// last character the parser consumed. Line and column are both 1-indexed,
+/// matching how editors report them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Position {
+    pub line: usize,
+    pub col: usize,
+    pub offset: usize,
+}
+
+impl Position {
+    /// Used for errors raised outside of parsing (e.g. typed extraction via
+    /// `FromJson`), where there's no cursor position to point at.
+    fn unknown() -> Self {
+        Self { line: 0, col: 0, offset: 0 }
+    }
+}
+
+/// A `JsonErrorKind` together with the position in the source at which it
+/// occurred.
+#[derive(Clone, Debug)]
+pub struct JsonError {
+    pub kind: JsonErrorKind,
+    pub position: Position,
+}
+
+impl JsonError {
+    fn new(kind: JsonErrorKind, position: Position) -> Self {
+        Self { kind, position }
+    }
+
+    fn at<T>(cursor: &Cursor, kind: JsonErrorKind) -> Result<T, JsonError> {
+        Err(JsonError::new(kind, cursor.current_position()))
+    }
+}
+
 /// Various variants that model JSON's data types into Rust counterparts.
 /// A new type distinction is made for floats and integers, which JSON
 /// does not do on its own..
@@ -133,6 +235,11 @@ pub enum JsonValue {
     /// The distinction exists on this end; JSON itself doesn't differentiate
     Integer(i64),
 
+    /// A non-fractional, non-negative JSON number too large to fit in an
+    /// `i64`, represented as a 64 bit unsigned Rust int. The distinction
+    /// exists on this end; JSON itself doesn't differentiate.
+    UInteger(u64),
+
     /// The billion dollar mistake.
     Null,
 
@@ -172,6 +279,13 @@ impl JsonValue {
         }
     }
 
+    pub fn get_uinteger(&self) -> Option<u64> {
+        match self {
+            JsonValue::UInteger(u) => Some(*u),
+            _ => None,
+        }
+    }
+
     pub fn get_float(&self) -> Option<f64> {
         match self {
             JsonValue::Float(f) => Some(*f),
@@ -209,6 +323,10 @@ impl JsonValue {
         self.get_integer().is_some()
     }
 
+    pub fn is_uinteger(&self) -> bool {
+        self.get_uinteger().is_some()
+    }
+
     pub fn is_float(&self) -> bool {
         self.get_float().is_some()
     }
@@ -222,7 +340,7 @@ impl JsonValue {
     }
 
     pub fn is_number(&self) -> bool {
-        self.is_integer() || self.is_float()
+        self.is_integer() || self.is_uinteger() || self.is_float()
     }
 
     pub fn is_primitive(&self) -> bool {
@@ -232,60 +350,329 @@ impl JsonValue {
     pub fn is_container(&self) -> bool {
         self.is_object() || self.is_array()
     }
+
+    /// Walks a sequence of object keys, returning the value at the end of
+    /// the path, or `None` if any segment is missing or not an object.
+    pub fn get_path(&self, path: &[&str]) -> Option<&JsonValue> {
+        let mut current = self;
+
+        for segment in path {
+            match current {
+                JsonValue::Object(map) => current = map.get(*segment)?,
+                _ => return None,
+            }
+        }
+
+        Some(current)
+    }
+
+    /// Extracts a typed value via `FromJson`, e.g.
+    /// `value.get_path(&["count"]).unwrap().get::<i64>()`.
+    pub fn get<T: FromJson>(&self) -> Result<T, JsonError> {
+        T::from_json(self)
+    }
+
+    /// Serializes back to compact JSON text, with no insignificant
+    /// whitespace between tokens.
+    pub fn serialize(&self) -> String {
+        let mut buffer = String::new();
+        self.write_compact(&mut buffer);
+        buffer
+    }
+
+    /// Serializes back to JSON text, indenting nested containers by
+    /// `indent` spaces per depth and placing each array element/object
+    /// pair on its own line.
+    pub fn serialize_pretty(&self, indent: usize) -> String {
+        let mut buffer = String::new();
+        self.write_pretty(&mut buffer, indent, 0);
+        buffer
+    }
+
+    fn write_compact(&self, buffer: &mut String) {
+        match self {
+            JsonValue::Null => buffer.push_str("null"),
+            JsonValue::Boolean(b) => buffer.push_str(if *b { "true" } else { "false" }),
+            JsonValue::Integer(i) => buffer.push_str(&i.to_string()),
+            JsonValue::UInteger(u) => buffer.push_str(&u.to_string()),
+            JsonValue::Float(f) if f.is_finite() => buffer.push_str(&format_float(*f)),
+            // `inf`/`-inf`/`NaN` have no JSON representation; `null` is
+            // what `JSON.stringify` does with them too, and it parses back
+            // (as `JsonValue::Null`) unlike emitting the token raw.
+            JsonValue::Float(_) => buffer.push_str("null"),
+            JsonValue::String(s) => escape_string(s, buffer),
+
+            JsonValue::Array(items) => {
+                buffer.push('[');
+
+                for (index, item) in items.iter().enumerate() {
+                    if index > 0 {
+                        buffer.push(',');
+                    }
+
+                    item.write_compact(buffer);
+                }
+
+                buffer.push(']');
+            }
+
+            JsonValue::Object(map) => {
+                buffer.push('{');
+
+                for (index, (key, value)) in map.iter().enumerate() {
+                    if index > 0 {
+                        buffer.push(',');
+                    }
+
+                    escape_string(key, buffer);
+                    buffer.push(':');
+                    value.write_compact(buffer);
+                }
+
+                buffer.push('}');
+            }
+        }
+    }
+
+    fn write_pretty(&self, buffer: &mut String, indent: usize, depth: usize) {
+        let pad = " ".repeat(indent * (depth + 1));
+        let closing_pad = " ".repeat(indent * depth);
+
+        match self {
+            JsonValue::Array(items) if !items.is_empty() => {
+                buffer.push_str("[\n");
+
+                for (index, item) in items.iter().enumerate() {
+                    if index > 0 {
+                        buffer.push_str(",\n");
+                    }
+
+                    buffer.push_str(&pad);
+                    item.write_pretty(buffer, indent, depth + 1);
+                }
+
+                buffer.push('\n');
+                buffer.push_str(&closing_pad);
+                buffer.push(']');
+            }
+
+            JsonValue::Object(map) if !map.is_empty() => {
+                buffer.push_str("{\n");
+
+                for (index, (key, value)) in map.iter().enumerate() {
+                    if index > 0 {
+                        buffer.push_str(",\n");
+                    }
+
+                    buffer.push_str(&pad);
+                    escape_string(key, buffer);
+                    buffer.push_str(": ");
+                    value.write_pretty(buffer, indent, depth + 1);
+                }
+
+                buffer.push('\n');
+                buffer.push_str(&closing_pad);
+                buffer.push('}');
+            }
+
+            // Scalars and empty containers have nothing to indent.
+            _ => self.write_compact(buffer),
+        }
+    }
+}
+
+/// Formats a finite float so it round-trips back through `parse_number`:
+/// always with a decimal point, so it's never mistaken for a
+/// `JsonValue::Integer`.
+fn format_float(value: f64) -> String {
+    let text = value.to_string();
+
+    if text.contains('.') || text.contains('e') {
+        text
+    } else {
+        format!("{}.0", text)
+    }
+}
+
+/// Writes `value` as a quoted JSON string, escaping `"`, `\`, the control
+/// characters with short escapes (`\n`, `\r`, `\t`, `\b`, `\f`), and any
+/// other control character as `\u00XX`.
+fn escape_string(value: &str, buffer: &mut String) {
+    buffer.push('"');
+
+    for c in value.chars() {
+        match c {
+            '"' => buffer.push_str("\\\""),
+            '\\' => buffer.push_str("\\\\"),
+            '\n' => buffer.push_str("\\n"),
+            '\r' => buffer.push_str("\\r"),
+            '\t' => buffer.push_str("\\t"),
+            '\u{0008}' => buffer.push_str("\\b"),
+            '\u{000C}' => buffer.push_str("\\f"),
+            c if (c as u32) < 0x20 => buffer.push_str(&format!("\\u{:04x}", c as u32)),
+            c => buffer.push(c),
+        }
+    }
+
+    buffer.push('"');
+}
+
+/// Converts a `&JsonValue` into a Rust type, failing with
+/// `JsonErrorKind::JsonValueNotType` when the value isn't the shape `Self`
+/// expects. Implemented for the primitive types plus `Vec<T>` and
+/// `HashMap<String, T>` for any `T: FromJson`, so a nested structure can be
+/// extracted with a single `value.get::<...>()` call.
+pub trait FromJson: Sized {
+    fn from_json(value: &JsonValue) -> Result<Self, JsonError>;
+}
+
+/// Builds the `JsonValueNotType` error raised by the `FromJson` impls below.
+fn not_type(value: &JsonValue, expected: &str) -> JsonError {
+    JsonError::new(
+        JsonErrorKind::JsonValueNotType(value.clone(), expected.to_string()),
+        Position::unknown(),
+    )
+}
+
+impl FromJson for String {
+    fn from_json(value: &JsonValue) -> Result<Self, JsonError> {
+        value.get_string().ok_or_else(|| not_type(value, "String"))
+    }
+}
+
+impl FromJson for i64 {
+    fn from_json(value: &JsonValue) -> Result<Self, JsonError> {
+        value.get_integer().ok_or_else(|| not_type(value, "i64"))
+    }
+}
+
+impl FromJson for u64 {
+    fn from_json(value: &JsonValue) -> Result<Self, JsonError> {
+        value.get_uinteger().ok_or_else(|| not_type(value, "u64"))
+    }
+}
+
+impl FromJson for f64 {
+    fn from_json(value: &JsonValue) -> Result<Self, JsonError> {
+        value.get_float().ok_or_else(|| not_type(value, "f64"))
+    }
+}
+
+impl FromJson for bool {
+    fn from_json(value: &JsonValue) -> Result<Self, JsonError> {
+        value.get_boolean().ok_or_else(|| not_type(value, "bool"))
+    }
+}
+
+impl<T: FromJson> FromJson for Vec<T> {
+    fn from_json(value: &JsonValue) -> Result<Self, JsonError> {
+        value
+            .get_array()
+            .ok_or_else(|| not_type(value, "Array"))?
+            .iter()
+            .map(T::from_json)
+            .collect()
+    }
+}
+
+impl<T: FromJson> FromJson for HashMap<String, T> {
+    fn from_json(value: &JsonValue) -> Result<Self, JsonError> {
+        value
+            .get_object()
+            .ok_or_else(|| not_type(value, "Object"))?
+            .iter()
+            .map(|(k, v)| T::from_json(v).map(|parsed| (k.clone(), parsed)))
+            .collect()
+    }
 }
 
-fn parse_number(head: char, tail: &mut Chars) -> Result<JsonValue, JsonError> {
+fn parse_number(head: char, tail: &mut Cursor) -> Result<JsonValue, JsonError> {
     let mut buffer: String = String::new();
 
     let mut floating_point: bool = false;
-    let mut complete_float: bool = false;
+    let mut has_exponent: bool = false;
 
     if !head.is_digit(10) && head != '-' {
-        return Err(JsonError::UnexpectedTokenCh(head));
+        return JsonError::at(tail, JsonErrorKind::UnexpectedTokenCh(head));
     }
 
     buffer.push(head);
 
     let mut previous = head;
 
-    while let Some(character) = tail.next() {
-        if character == ',' || character == '}' || character == ']' {
+    // Peek rather than consume: the terminating delimiter (`,`/`}`/`]`) or
+    // whitespace belongs to whatever called us (an array, object, or the
+    // top-level parse), not to the number itself.
+    while let Some(&character) = tail.peek() {
+        if character == ',' || character == '}' || character == ']'
+            || character.is_whitespace() || character.is_control()
+        {
             break;
         } else if character == '.' {
-            if floating_point {
-                return Err(JsonError::OverOneDecimalPoint);
+            if has_exponent {
+                return JsonError::at(tail, JsonErrorKind::DecimalPointPlacedAfter(previous));
+            } else if floating_point {
+                return JsonError::at(tail, JsonErrorKind::OverOneDecimalPoint);
             } else if !floating_point && !previous.is_digit(10) {
-                return Err(JsonError::DecimalPointPlacedAfter(previous));
+                return JsonError::at(tail, JsonErrorKind::DecimalPointPlacedAfter(previous));
             }
 
             floating_point = true;
             buffer.push(character);
+            previous = character;
+            tail.next();
+        } else if character == 'e' || character == 'E' {
+            if has_exponent || !previous.is_digit(10) {
+                return JsonError::at(tail, JsonErrorKind::BadExponent);
+            }
+
+            has_exponent = true;
+            floating_point = true;
+            buffer.push(character);
+            previous = character;
+            tail.next();
+        } else if character == '+' || character == '-' {
+            if previous != 'e' && previous != 'E' {
+                return JsonError::at(tail, JsonErrorKind::UnexpectedTokenCh(character));
+            }
+
+            buffer.push(character);
+            previous = character;
+            tail.next();
         } else if character.is_digit(10) {
             buffer.push(character);
             previous = character;
+            tail.next();
         } else {
-            // This and the above else if **must** come after the first two
-            // since the first two cases qualify as non-digit characters, but
-            // are deliberately exempt. Preserve the order if refactoring.
+            // This and the above else ifs **must** come after the digit
+            // check since earlier cases qualify as non-digit characters,
+            // but are deliberately exempt. Preserve the order if
+            // refactoring.
 
-            return Err(JsonError::UnexpectedTokenCh(character));
+            return JsonError::at(tail, JsonErrorKind::UnexpectedTokenCh(character));
         }
     }
 
+    let position = tail.current_position();
+
     if floating_point {
         buffer
             .parse::<f64>()
-            .map_err(|e| JsonError::InconvertibleToFloat(buffer, e))
+            .map_err(|e| JsonError::new(JsonErrorKind::InconvertibleToFloat(buffer, e), position))
             .map(|f| JsonValue::Float(f))
+    } else if let Ok(i) = buffer.parse::<i64>() {
+        Ok(JsonValue::Integer(i))
+    } else if let Ok(u) = buffer.parse::<u64>() {
+        Ok(JsonValue::UInteger(u))
     } else {
         buffer
-            .parse::<i64>()
-            .map_err(|e| JsonError::InconvertibleToInt(buffer, e))
-            .map(|f| JsonValue::Integer(f))
+            .parse::<f64>()
+            .map_err(|e| JsonError::new(JsonErrorKind::InconvertibleToFloat(buffer, e), position))
+            .map(|f| JsonValue::Float(f))
     }
 }
 
-fn parse_boolean(head: char, tail: &mut Chars) -> Result<JsonValue, JsonError> {
+fn parse_boolean(head: char, tail: &mut Cursor) -> Result<JsonValue, JsonError> {
     match head {
         't' if tail.take(3).collect::<String>() == "rue" => {
             return Ok(JsonValue::Boolean(true));
@@ -295,23 +682,21 @@ fn parse_boolean(head: char, tail: &mut Chars) -> Result<JsonValue, JsonError> {
             return Ok(JsonValue::Boolean(false));
         }
 
-        _ => {
-            return Err(JsonError::UnexpectedToken);
-        }
+        _ => JsonError::at(tail, JsonErrorKind::UnexpectedToken),
     }
 }
 
-fn parse_string(head: char, tail: &mut Chars) -> Result<JsonValue, JsonError> {
+fn parse_string(head: char, tail: &mut Cursor) -> Result<JsonValue, JsonError> {
     let mut buffer: String = String::new();
 
     if head != '"' {
-        return Err(JsonError::UnexpectedToken);
+        return JsonError::at(tail, JsonErrorKind::UnexpectedToken);
     }
 
     while let Some(character) = tail.next() {
         match character {
             '"' => {
-                return Ok(JsonValue::String(format!("{}{}", head, buffer)));
+                return Ok(JsonValue::String(buffer));
             }
 
             '\\' => match tail.next() {
@@ -320,99 +705,206 @@ fn parse_string(head: char, tail: &mut Chars) -> Result<JsonValue, JsonError> {
                 Some('t') => buffer.push('\t'),
                 Some('b') => buffer.push('\u{0008}'),
                 Some('f') => buffer.push('\u{000C}'),
+                Some('u') => buffer.push(parse_unicode_escape(tail)?),
                 Some(c) => buffer.push(c),
-                None => return Err(JsonError::UnexpectedEndOfInput),
+                None => return JsonError::at(tail, JsonErrorKind::UnexpectedEndOfInput),
             },
 
             c => buffer.push(c),
         }
     }
 
-    Ok(JsonValue::Null)
+    JsonError::at(tail, JsonErrorKind::UnexpectedEndOfInput)
 }
 
-fn parse_json(json: String) -> Result<JsonValue, JsonError> {
-    let mut iter = json.chars();
-    parse_value(&mut iter)
-}
+/// Reads exactly four hex digits from `tail` (the part after `\u`) and
+/// returns them as a UTF-16 code unit.
+fn read_hex4(tail: &mut Cursor) -> Result<u16, JsonError> {
+    let mut digits = String::with_capacity(4);
 
-/// Deduces the type of JSON value from the first character, and delegates
-/// to the appropriate function to parse the rest of the value.
-fn parse_value(iter: &mut Chars) -> Result<JsonValue, JsonError> {
-    while let Some(character) = iter.next() {
-        match character {
-            c if c.is_whitespace() || c.is_control() => continue,
-            't' | 'f' => return parse_boolean(character, iter),
-            '"' => return parse_string(character, iter),
-            '0'..='9' | '-' => return parse_number(character, iter),
-            '{' => return parse_object(character, iter),
-            '[' => return parse_array(character, iter),
-            _ => return Err(JsonError::UnexpectedTokenCh(character)),
+    for _ in 0..4 {
+        match tail.next() {
+            Some(c) => digits.push(c),
+            None => return JsonError::at(tail, JsonErrorKind::UnexpectedEndOfInput),
         }
     }
 
-    return Err(JsonError::UnexpectedEndOfInput);
+    u16::from_str_radix(&digits, 16)
+        .map_err(|_| JsonError::new(JsonErrorKind::BadUnicodeEscape(digits), tail.current_position()))
 }
 
-// ,[1, 2, {'foo': 'bar'}, true, false, null, 3.14, -3.1, [[4], 1]]
-fn parse_array(head: char, tail: &mut Chars) -> Result<JsonValue, JsonError> {
-    let mut buffer: Vec<JsonValue> = Vec::new();
+/// Parses a `\uXXXX` escape (the `\u` already consumed), combining a high
+/// surrogate with the low surrogate escape that must immediately follow it.
+fn parse_unicode_escape(tail: &mut Cursor) -> Result<char, JsonError> {
+    let unit = read_hex4(tail)?;
+
+    if (0xD800..0xDC00).contains(&unit) {
+        match (tail.next(), tail.next()) {
+            (Some('\\'), Some('u')) => {
+                let low = read_hex4(tail)?;
+
+                if !(0xDC00..0xE000).contains(&low) {
+                    return JsonError::at(
+                        tail,
+                        JsonErrorKind::BadUnicodeEscape(format!("{:04x}\\u{:04x}", unit, low)),
+                    );
+                }
 
-    while let Some(c) = tail.next() {
-        match c {
-            _ if c.is_whitespace() || c.is_control() => continue,
-            ']' => return Ok(JsonValue::Array(buffer)),
-            ',' => continue,
-            _ => buffer.push(parse_value(tail)?),
+                let combined = 0x10000 + ((unit as u32 - 0xD800) << 10) + (low as u32 - 0xDC00);
+
+                char::from_u32(combined)
+                    .ok_or_else(|| JsonError::new(JsonErrorKind::BadUnicodeEscape(format!("{:04x}", combined)), tail.current_position()))
+            }
+
+            _ => JsonError::at(tail, JsonErrorKind::BadUnicodeEscape(format!("{:04x}", unit))),
         }
+    } else if (0xDC00..0xE000).contains(&unit) {
+        JsonError::at(tail, JsonErrorKind::BadUnicodeEscape(format!("{:04x}", unit)))
+    } else {
+        char::from_u32(unit as u32)
+            .ok_or_else(|| JsonError::new(JsonErrorKind::BadUnicodeEscape(format!("{:04x}", unit)), tail.current_position()))
     }
+}
 
-    Ok(JsonValue::Array(buffer))
+fn parse_null(head: char, tail: &mut Cursor) -> Result<JsonValue, JsonError> {
+    match head {
+        'n' if tail.take(3).collect::<String>() == "ull" => Ok(JsonValue::Null),
+        _ => JsonError::at(tail, JsonErrorKind::UnexpectedToken),
+    }
 }
 
-fn parse_object(head: char, tail: &mut Chars) -> Result<JsonValue, JsonError> {
-    let mut buffer: HashMap<String, JsonValue> = HashMap::new();
+/// Advances past any whitespace/control characters and returns the next
+/// significant one, or `None` if the input ran out first.
+fn skip_insignificant(iter: &mut Cursor) -> Option<char> {
+    iter.find(|c| !c.is_whitespace() && !c.is_control())
+}
 
-    if head != '{' {
-        return Err(JsonError::UnexpectedToken);
+/// Deduces the type of JSON value from its already-consumed first
+/// character, and delegates to the appropriate function to parse the rest
+/// of it. Shared by `parse_value` (which consumes that first character
+/// itself) and the array/object parsers (which have to peek past
+/// commas/whitespace to find it, and so consume it first).
+fn dispatch_value(head: char, tail: &mut Cursor) -> Result<JsonValue, JsonError> {
+    match head {
+        't' | 'f' => parse_boolean(head, tail),
+        'n' => parse_null(head, tail),
+        '"' => parse_string(head, tail),
+        '0'..='9' | '-' => parse_number(head, tail),
+        '{' => parse_object(head, tail),
+        '[' => parse_array(head, tail),
+        _ => JsonError::at(tail, JsonErrorKind::UnexpectedTokenCh(head)),
     }
+}
 
-    let mut key: Option<String> = None;
-    let mut value: Option<JsonValue> = None;
+fn parse_json(json: &str) -> Result<JsonValue, JsonError> {
+    let mut cursor = Cursor::new(json);
+    parse_value(&mut cursor)
+}
 
-    let mut awaiting_val = false;
-    let mut awiating_col = false;
+/// Parses a complete JSON document from raw bytes.
+pub fn parse(data: &[u8]) -> Result<JsonValue, JsonError> {
+    parse_json(&String::from_utf8_lossy(data))
+}
 
-    while let Some(character) = tail.next() {
-        if character.is_whitespace() || character.is_control() {
-            continue;
+fn parse_value(iter: &mut Cursor) -> Result<JsonValue, JsonError> {
+    match skip_insignificant(iter) {
+        Some(character) => dispatch_value(character, iter),
+        None => JsonError::at(iter, JsonErrorKind::UnexpectedEndOfInput),
+    }
+}
+
+// ,[1, 2, {'foo': 'bar'}, true, false, null, 3.14, -3.1, [[4], 1]]
+fn parse_array(head: char, tail: &mut Cursor) -> Result<JsonValue, JsonError> {
+    if head != '[' {
+        return JsonError::at(tail, JsonErrorKind::UnexpectedToken);
+    }
+
+    let mut buffer: Vec<JsonValue> = Vec::new();
+    let mut expect_comma = false;
+
+    loop {
+        let next = match skip_insignificant(tail) {
+            Some(c) => c,
+            None => return JsonError::at(tail, JsonErrorKind::UnexpectedEndOfInput),
+        };
+
+        if next == ']' {
+            return Ok(JsonValue::Array(buffer));
         }
 
-        match (character, &key, &value) {
-            (':', Some(k), None) => {
-                buffer.insert(k.clone(), parse_value(tail)?);
-                key = None;
+        if expect_comma {
+            if next != ',' {
+                return JsonError::at(tail, JsonErrorKind::UnexpectedTokenCh(next));
             }
 
-            (',' | '}', Some(k), Some(v)) => {
-                buffer.insert(k.clone(), v.clone());
+            expect_comma = false;
+            continue;
+        }
 
-                key = None;
-                value = None;
+        buffer.push(dispatch_value(next, tail)?);
+        expect_comma = true;
+    }
+}
+
+fn parse_object(head: char, tail: &mut Cursor) -> Result<JsonValue, JsonError> {
+    if head != '{' {
+        return JsonError::at(tail, JsonErrorKind::UnexpectedToken);
+    }
+
+    let mut buffer: HashMap<String, JsonValue> = HashMap::new();
+    let mut expect_comma = false;
+    let mut after_comma = false;
+
+    loop {
+        let next = match skip_insignificant(tail) {
+            Some(c) => c,
+            None => return JsonError::at(tail, JsonErrorKind::UnexpectedEndOfInput),
+        };
+
+        if next == '}' {
+            if after_comma {
+                return JsonError::at(tail, JsonErrorKind::UnexpectedTokenCh(next));
             }
 
-            ('"', None, None) => {
-                if let Some(s) = parse_string(head, tail)?.get_string() {
-                    key = Some(s);
-                    awaiting_val = true;
-                }
+            return Ok(JsonValue::Object(buffer));
+        }
+
+        if expect_comma {
+            if next != ',' {
+                return JsonError::at(tail, JsonErrorKind::UnexpectedTokenCh(next));
             }
 
-            _ => {}
+            expect_comma = false;
+            after_comma = true;
+            continue;
+        }
+
+        if next != '"' {
+            return JsonError::at(tail, JsonErrorKind::UnexpectedTokenCh(next));
         }
-    }
 
-    todo!();
+        let key = match parse_string(next, tail)? {
+            JsonValue::String(s) => s,
+            _ => unreachable!(),
+        };
+
+        let colon = match skip_insignificant(tail) {
+            Some(c) => c,
+            None => return JsonError::at(tail, JsonErrorKind::UnexpectedEndOfInput),
+        };
+
+        if colon != ':' {
+            return JsonError::at(tail, JsonErrorKind::UnexpectedTokenCh(colon));
+        }
+
+        if buffer.contains_key(&key) {
+            return JsonError::at(tail, JsonErrorKind::UnexpectedTokenSt(key));
+        }
+
+        buffer.insert(key, parse_value(tail)?);
+        expect_comma = true;
+        after_comma = false;
+    }
 }
 
 //
@@ -575,31 +1067,64 @@ mod tests {
 
     #[test]
     fn test_parse_number() {
-        assert!(match parse_number('1', &mut "234".chars()) {
+        assert!(match parse_number('1', &mut Cursor::new("234")) {
             Ok(JsonValue::Integer(s)) if s == 1234 => true,
             _ => false,
         });
 
-        assert!(match parse_number('-', &mut "123".chars()) {
+        assert!(match parse_number('-', &mut Cursor::new("123")) {
             Ok(JsonValue::Integer(v)) if -123 == v => true,
             _ => false,
         });
 
-        assert!(match parse_number('1', &mut "23.4".chars()) {
+        assert!(match parse_number('1', &mut Cursor::new("23.4")) {
             Ok(JsonValue::Float(v)) if 123.4 == v => true,
             _ => false,
         });
 
-        assert!(match parse_number('-', &mut "123.4".chars()) {
+        assert!(match parse_number('-', &mut Cursor::new("123.4")) {
             Ok(JsonValue::Float(v)) if -123.4 == v => true,
             _ => false,
         });
     }
 
+    #[test]
+    fn test_parse_number_scientific() {
+        assert!(match parse_number('1', &mut Cursor::new(".2e-45")) {
+            Ok(JsonValue::Float(v)) if v == 1.2e-45 => true,
+            _ => false,
+        });
+
+        assert!(match parse_number('1', &mut Cursor::new("2e+3")) {
+            Ok(JsonValue::Float(v)) if v == 12e+3 => true,
+            _ => false,
+        });
+
+        assert!(match parse_number('1', &mut Cursor::new("2E3")) {
+            Ok(JsonValue::Float(v)) if v == 12e3 => true,
+            _ => false,
+        });
+
+        assert!(matches!(
+            parse_number('1', &mut Cursor::new("e2e3")),
+            Err(JsonError { kind: JsonErrorKind::BadExponent, .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_number_uinteger_overflow() {
+        // One past i64::MAX, so it must fall back to UInteger rather than
+        // erroring or silently becoming a Float.
+        assert!(match parse_number('9', &mut Cursor::new("223372036854775808")) {
+            Ok(JsonValue::UInteger(v)) if v == 9223372036854775808u64 => true,
+            _ => false,
+        });
+    }
+
     #[test]
     fn test_parse_string() {
 
-        assert!(match parse_string('"', &mut "test\"".chars()) {
+        assert!(match parse_string('"', &mut Cursor::new("test\"")) {
             Ok(JsonValue::String(s)) => {
                 println!("S Is: {}", s);
                 s == "test".to_string()
@@ -607,7 +1132,7 @@ mod tests {
             _ => false,
         });
 
-        // assert!(match parse_string('"', &mut "\\\"test\\\"".chars()) {
+        // assert!(match parse_string('"', &mut Cursor::new("\\\"test\\\"")) {
         //     Ok(JsonValue::String(s)) => s == "\"test\"".to_string(),
         //     _ => false,
         // });
@@ -615,11 +1140,11 @@ mod tests {
 
     #[test]
     fn test_parse_boolean() {
-        assert!(match parse_boolean('t', &mut "rue".chars()) {
+        assert!(match parse_boolean('t', &mut Cursor::new("rue")) {
             Ok(JsonValue::Boolean(s)) => s == true,
             _ => false,
         });
-        assert!(match parse_boolean('f', &mut "alse".chars()) {
+        assert!(match parse_boolean('f', &mut Cursor::new("alse")) {
             Ok(JsonValue::Boolean(s)) => s == false,
             _ => false,
         });
@@ -627,7 +1152,7 @@ mod tests {
 
     #[test]
     fn test_parse_array() {
-        assert!(match parse_array('[', &mut "[1, 2, 3]".chars()) {
+        assert!(match parse_array('[', &mut Cursor::new("1, 2, 3]")) {
             Ok(JsonValue::Array(vjv)) => vjv
                 .into_iter()
                 .zip(vec![1, 2, 3])
@@ -641,7 +1166,7 @@ mod tests {
         let mut test_map: HashMap<String, String> = HashMap::new();
         test_map.insert("key".into(), "value".into());
 
-        assert!(match parse_object('{', &mut "\"key\": \"value\" }".chars()) {
+        assert!(match parse_object('{', &mut Cursor::new("\"key\": \"value\" }")) {
             Ok(JsonValue::Object(mjv)) => {
                 mjv.into_iter().all( |(k, v)| matches!(v.get_string(), Some(s) if s == *test_map.get(&k).unwrap()))
             },
@@ -655,7 +1180,7 @@ mod tests {
         let mut test_map: HashMap<String, i64> = HashMap::new();
         test_map.insert("key".into(), 1234i64);
 
-        assert!(match parse_object('{', &mut "\"key\": 1234 }".chars()) {
+        assert!(match parse_object('{', &mut Cursor::new("\"key\": 1234 }")) {
             Ok(JsonValue::Object(mjv)) => {
                 mjv.into_iter().all(
                     |(k, v)| matches!(v.get_integer(), Some(s) if s == *test_map.get(&k).unwrap()),
@@ -671,7 +1196,7 @@ mod tests {
         let mut test_map: HashMap<String, f64> = HashMap::new();
         test_map.insert("key".into(), 12.34f64);
 
-        assert!(match parse_object('{', &mut "\"key\": 12.34 }".chars()) {
+        assert!(match parse_object('{', &mut Cursor::new("\"key\": 12.34 }")) {
             Ok(JsonValue::Object(mjv)) => {
                 mjv.into_iter().all(
                     |(k, v)| matches!(v.get_float(), Some(s) if s == *test_map.get(&k).unwrap()),
@@ -689,7 +1214,7 @@ mod tests {
         test_map.insert("key1".into(), true);
         test_map.insert("key2".into(), false);
 
-        assert!(match parse_object('{', &mut "\"key1\": true, \"key2\": false }".chars()) {
+        assert!(match parse_object('{', &mut Cursor::new("\"key1\": true, \"key2\": false }")) {
             Ok(JsonValue::Object(mjv)) => {
                 mjv.into_iter().all(
                     |(k, v)| matches!(v.get_boolean(), Some(s) if s == *test_map.get(&k).unwrap()),
@@ -707,7 +1232,7 @@ mod tests {
 
         test_map.insert("key".into(), test_vec.clone());
 
-        assert!(match parse_object('{', &mut "\"key\": [1, 2, 3] }".chars()) {
+        assert!(match parse_object('{', &mut Cursor::new("\"key\": [1, 2, 3] }")) {
             Ok(JsonValue::Object(mjv)) => {
                 mjv.into_iter().all( |(_, v)| -> bool {
                     let v = match v {
@@ -722,6 +1247,79 @@ mod tests {
             _ => false,
         });
     }
+
+    #[test]
+    fn test_from_json() {
+        let parsed = parse_json("{\"name\": \"sto\", \"tags\": [\"a\", \"b\"]}").unwrap();
+
+        let name: String = parsed.get_path(&["name"]).unwrap().get::<String>().unwrap();
+        assert_eq!(name, "sto");
+
+        let tags: Vec<String> = parsed.get_path(&["tags"]).unwrap().get::<Vec<String>>().unwrap();
+        assert_eq!(tags, vec!["a".to_string(), "b".to_string()]);
+
+        assert!(parsed.get_path(&["name"]).unwrap().get::<i64>().is_err());
+    }
+
+    #[test]
+    fn test_parse_string_unicode_escape() {
+        assert!(match parse_string('"', &mut Cursor::new("\\u0041\"")) {
+            Ok(JsonValue::String(s)) => s == "A",
+            _ => false,
+        });
+
+        // A surrogate pair for U+1F600 (grinning face).
+        assert!(match parse_string('"', &mut Cursor::new("\\ud83d\\ude00\"")) {
+            Ok(JsonValue::String(s)) => s == "\u{1F600}",
+            _ => false,
+        });
+
+        assert!(matches!(
+            parse_string('"', &mut Cursor::new("\\ud83d\"")),
+            Err(JsonError { kind: JsonErrorKind::BadUnicodeEscape(_), .. })
+        ));
+    }
+
+    #[test]
+    fn test_error_position() {
+        // The `?` is the last character read before the error is raised, so
+        // the cursor (which always points at the next unread character) has
+        // already moved past it and onto the following newline.
+        let err = parse_json("{\n  \"key\": ?\n}").unwrap_err();
+
+        assert_eq!(err.position.line, 2);
+        assert_eq!(err.position.col, 11);
+    }
+
+    #[test]
+    fn test_serialize_compact() {
+        assert_eq!(JsonValue::Null.serialize(), "null");
+        assert_eq!(JsonValue::Boolean(true).serialize(), "true");
+        assert_eq!(JsonValue::Integer(42).serialize(), "42");
+        assert_eq!(JsonValue::Float(1.5).serialize(), "1.5");
+        assert_eq!(JsonValue::Float(2.0).serialize(), "2.0");
+        assert_eq!(JsonValue::String("a\"b".into()).serialize(), "\"a\\\"b\"");
+        assert_eq!(
+            JsonValue::Array(vec![JsonValue::Integer(1), JsonValue::Integer(2)]).serialize(),
+            "[1,2]"
+        );
+    }
+
+    #[test]
+    fn test_serialize_non_finite_float() {
+        assert_eq!(JsonValue::Float(f64::INFINITY).serialize(), "null");
+        assert_eq!(JsonValue::Float(f64::NEG_INFINITY).serialize(), "null");
+        assert_eq!(JsonValue::Float(f64::NAN).serialize(), "null");
+    }
+
+    #[test]
+    fn test_serialize_round_trip() {
+        let source = "{\"a\":[1,2.5,true,null,\"text\"]}";
+        let parsed = parse_json(source).unwrap();
+        let reparsed = parse_json(&parsed.serialize()).unwrap();
+
+        assert_eq!(parsed.serialize(), reparsed.serialize());
+    }
 }
 // const NEG: i32 = 0b1000_0000_0000_0000_0000_0000_0000_0000_u32 as i32;
 //
@@ -4,7 +4,9 @@ mod api;
 mod error;
 mod gzip;
 mod http;
+mod json;
 mod search;
+mod tls;
 
 use api::ServerStatus;
 use error::Error;
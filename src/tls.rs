@@ -0,0 +1,172 @@
+use std::{
+    ffi::{c_void, CString},
+    io::{self, Read, Write},
+    os::raw::{c_int, c_long},
+    os::unix::io::AsRawFd,
+};
+
+use crate::error::Error;
+
+// Opaque handles; we never touch their fields directly, only pass the
+// pointers OpenSSL hands back into the next call, same treatment as
+// `ZStream`'s `state` pointer in gzip.rs.
+type SslMethod = c_void;
+type SslCtx = c_void;
+type Ssl = c_void;
+
+/// `SSL_VERIFY_PEER`: fail the handshake if the peer's certificate doesn't
+/// verify, instead of OpenSSL's client default of accepting anything.
+const SSL_VERIFY_PEER: c_int = 1;
+
+/// `X509_V_OK`: the value `SSL_get_verify_result` returns when certificate
+/// verification succeeded.
+const X509_V_OK: c_long = 0;
+
+#[link(name = "ssl")]
+#[link(name = "crypto")]
+extern "C" {
+    fn TLS_client_method() -> *const SslMethod;
+    fn SSL_CTX_new(method: *const SslMethod) -> *mut SslCtx;
+    fn SSL_CTX_free(ctx: *mut SslCtx);
+    fn SSL_CTX_set_verify(
+        ctx: *mut SslCtx,
+        mode: c_int,
+        callback: Option<extern "C" fn(c_int, *mut c_void) -> c_int>,
+    );
+    fn SSL_CTX_set_default_verify_paths(ctx: *mut SslCtx) -> c_int;
+    fn SSL_new(ctx: *mut SslCtx) -> *mut Ssl;
+    fn SSL_set_fd(ssl: *mut Ssl, fd: c_int) -> c_int;
+    fn SSL_set1_host(ssl: *mut Ssl, hostname: *const std::os::raw::c_char) -> c_int;
+    fn SSL_connect(ssl: *mut Ssl) -> c_int;
+    fn SSL_get_verify_result(ssl: *const Ssl) -> c_long;
+    fn SSL_read(ssl: *mut Ssl, buf: *mut u8, num: c_int) -> c_int;
+    fn SSL_write(ssl: *mut Ssl, buf: *const u8, num: c_int) -> c_int;
+    fn SSL_free(ssl: *mut Ssl);
+}
+
+/// A TLS-wrapped socket, handed an already-connected `TcpStream`.
+///
+/// This owns the `SSL_CTX`/`SSL` handles for the lifetime of the
+/// connection and frees both on drop, mirroring the init/use/teardown
+/// shape of `gzip::inflate`.
+pub struct TlsStream {
+    stream: std::net::TcpStream,
+    ctx: *mut SslCtx,
+    ssl: *mut Ssl,
+}
+
+impl TlsStream {
+    /// Performs the TLS handshake over `stream`, which must already be a
+    /// connected `TcpStream`. Keeps `stream` alive for the lifetime of the
+    /// session since `SSL_set_fd` only borrows the raw descriptor.
+    ///
+    /// `hostname` is checked against the peer certificate (via
+    /// `SSL_set1_host`) so the connection fails closed against a MITM
+    /// presenting a certificate for a different name, rather than trusting
+    /// whatever OpenSSL's client default (`SSL_VERIFY_NONE`) would accept.
+    pub fn connect(stream: std::net::TcpStream, hostname: &str) -> Result<Self, Error> {
+        unsafe {
+            let method = TLS_client_method();
+
+            if method.is_null() {
+                return Err(Error::TlsError("TLS_client_method returned null".into()));
+            }
+
+            let ctx = SSL_CTX_new(method);
+
+            if ctx.is_null() {
+                return Err(Error::TlsError("SSL_CTX_new returned null".into()));
+            }
+
+            SSL_CTX_set_verify(ctx, SSL_VERIFY_PEER, None);
+
+            if SSL_CTX_set_default_verify_paths(ctx) != 1 {
+                SSL_CTX_free(ctx);
+                return Err(Error::TlsError(
+                    "SSL_CTX_set_default_verify_paths failed".into(),
+                ));
+            }
+
+            let ssl = SSL_new(ctx);
+
+            if ssl.is_null() {
+                SSL_CTX_free(ctx);
+                return Err(Error::TlsError("SSL_new returned null".into()));
+            }
+
+            let hostname_c = match CString::new(hostname) {
+                Ok(s) => s,
+                Err(_) => {
+                    SSL_free(ssl);
+                    SSL_CTX_free(ctx);
+                    return Err(Error::TlsError("hostname contains a null byte".into()));
+                }
+            };
+
+            if SSL_set1_host(ssl, hostname_c.as_ptr()) != 1 {
+                SSL_free(ssl);
+                SSL_CTX_free(ctx);
+                return Err(Error::TlsError("SSL_set1_host failed".into()));
+            }
+
+            if SSL_set_fd(ssl, stream.as_raw_fd()) != 1 {
+                SSL_free(ssl);
+                SSL_CTX_free(ctx);
+                return Err(Error::TlsError("SSL_set_fd failed".into()));
+            }
+
+            if SSL_connect(ssl) != 1 {
+                SSL_free(ssl);
+                SSL_CTX_free(ctx);
+                return Err(Error::TlsError("SSL_connect handshake failed".into()));
+            }
+
+            if SSL_get_verify_result(ssl) != X509_V_OK {
+                SSL_free(ssl);
+                SSL_CTX_free(ctx);
+                return Err(Error::TlsError(
+                    "certificate verification failed".into(),
+                ));
+            }
+
+            Ok(Self { stream, ctx, ssl })
+        }
+    }
+}
+
+impl Read for TlsStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let nread = unsafe { SSL_read(self.ssl, buf.as_mut_ptr(), buf.len() as c_int) };
+
+        if nread < 0 {
+            return Err(io::Error::new(io::ErrorKind::Other, "SSL_read failed"));
+        }
+
+        Ok(nread as usize)
+    }
+}
+
+impl Write for TlsStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let nwritten = unsafe { SSL_write(self.ssl, buf.as_ptr(), buf.len() as c_int) };
+
+        if nwritten < 0 {
+            return Err(io::Error::new(io::ErrorKind::Other, "SSL_write failed"));
+        }
+
+        Ok(nwritten as usize)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for TlsStream {
+    fn drop(&mut self) {
+        unsafe {
+            SSL_free(self.ssl);
+            SSL_CTX_free(self.ctx);
+        }
+    }
+}